@@ -2,17 +2,162 @@
 use crate::runtime::{Handle, Spawner, time, io};
 
 use std::cell::RefCell;
+use std::fmt;
 
 thread_local! {
-    static CONTEXT: RefCell<Option<Handle>> = RefCell::new(None)
+    static CONTEXT: RefCell<Option<ThreadContext>> = RefCell::new(None)
+}
+
+/// The [`Handle`] active on this thread, together with a marker recording
+/// how it came to be entered.
+///
+/// Keeping the two together lets [`enter_context`] tell a worker thread
+/// driving async tasks apart from a thread that is blocked inside
+/// `block_on`, without a second, easy-to-desync thread-local.
+#[derive(Debug, Clone)]
+struct ThreadContext {
+    handle: Handle,
+    enter: EnterContext,
+}
+
+/// How the [`Handle`] on this thread was entered.
+///
+/// `block_on` consults this to detect the case where it is called again
+/// while the current thread is already blocked driving another `block_on`
+/// future, which would otherwise deadlock the thread silently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EnterContext {
+    /// Entered via `Runtime::block_on`, blocking the thread until the
+    /// future completes.
+    BlockOn,
+    /// Entered as a worker thread driving the runtime, or any other
+    /// context that only needs the handle without blocking the thread.
+    Worker,
+    /// Entered on a `spawn_blocking` pool thread, running the ambient
+    /// [`Handle`] captured from the thread that submitted the blocking
+    /// closure.
+    Blocking,
+}
+
+/// Retrieve the [`Handle`] of the runtime that is currently executing on
+/// this thread.
+///
+/// This function panics if called outside the context of a Tokio runtime.
+/// Use [`try_current`] for a non-panicking variant.
+pub(crate) fn current() -> Handle {
+    match try_current() {
+        Ok(handle) => handle,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+/// Retrieve the [`Handle`] of the runtime that is currently executing on
+/// this thread, returning an error if there is none.
+pub(crate) fn try_current() -> Result<Handle, TryCurrentError> {
+    CONTEXT.with(|ctx| match *ctx.borrow() {
+        Some(ref ctx) => Ok(ctx.handle.clone()),
+        None => Err(TryCurrentError(())),
+    })
+}
+
+/// Returns how the [`Handle`] on this thread was entered, if any.
+pub(crate) fn enter_context() -> Option<EnterContext> {
+    CONTEXT.with(|ctx| ctx.borrow().as_ref().map(|ctx| ctx.enter))
+}
+
+/// Panics if the current thread is already inside a call to
+/// `Runtime::block_on`.
+///
+/// Blocking a thread that is itself driving a `block_on` future deadlocks
+/// silently; this turns that hang into an immediate, actionable panic.
+pub(crate) fn assert_no_nested_block_on() {
+    if let Some(EnterContext::BlockOn) = enter_context() {
+        panic!(
+            "Cannot start a runtime from within a runtime. This happens \
+             because a function (like `block_on`) attempted to block the \
+             current thread while the thread is being used to drive \
+             asynchronous tasks."
+        );
+    }
+}
+
+/// Error returned by [`try_current`] when there is no runtime context on
+/// the current thread.
+#[derive(Debug)]
+pub struct TryCurrentError(());
+
+impl fmt::Display for TryCurrentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(
+            "there is no reactor running, must be called from the context of a Tokio 1.x runtime",
+        )
+    }
+}
+
+impl std::error::Error for TryCurrentError {}
+
+impl Handle {
+    /// Returns a `Handle` view over the currently running `Runtime`.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if called outside the context of a Tokio runtime.
+    pub fn current() -> Self {
+        current()
+    }
+
+    /// Returns a `Handle` view over the currently running `Runtime`.
+    ///
+    /// Returns an error if the current thread is not in the context of a
+    /// `Runtime`.
+    pub fn try_current() -> Result<Self, TryCurrentError> {
+        try_current()
+    }
+
+    /// Enters the runtime context for the lifetime of the returned
+    /// [`EnterGuard`].
+    ///
+    /// This is useful when the region that needs a runtime context spans
+    /// multiple statements, or crosses an `async` boundary that cannot be
+    /// expressed as a single closure passed to a scoped `enter` call.
+    pub fn enter(&self) -> EnterGuard {
+        EnterGuard(set(self.clone(), EnterContext::Worker))
+    }
+}
+
+/// Guard returned by [`Handle::enter`] that restores the previous runtime
+/// context when dropped.
+#[derive(Debug)]
+pub struct EnterGuard(Option<ThreadContext>);
+
+impl Drop for EnterGuard {
+    fn drop(&mut self) {
+        CONTEXT.with(|ctx| {
+            *ctx.borrow_mut() = self.0.take();
+        });
+    }
+}
+
+/// Set `handle` as the current runtime, returning the previously set
+/// [`ThreadContext`], if any, so it can be restored later.
+fn set(handle: Handle, enter: EnterContext) -> Option<ThreadContext> {
+    CONTEXT.with(|ctx| ctx.borrow_mut().replace(ThreadContext { handle, enter }))
 }
 
 /// Set the currently active runtime for the duration of the closure.
-pub(crate) fn enter<F>(handle: &Handle, f: F) -> R
+///
+/// Panics if `enter_context` is [`EnterContext::BlockOn`] and the current
+/// thread is already inside a call to `Runtime::block_on`; see
+/// [`assert_no_nested_block_on`].
+pub(crate) fn enter<F, R>(handle: &Handle, enter_context: EnterContext, f: F) -> R
 where
     F: FnOnce() -> R
 {
-    struct Reset(Option<Handle>);
+    if let EnterContext::BlockOn = enter_context {
+        assert_no_nested_block_on();
+    }
+
+    struct Reset(Option<ThreadContext>);
 
     impl Drop for Reset {
         fn drop(&mut self) {
@@ -22,22 +167,43 @@ where
         }
     }
 
-    let _reset = CONTEXT.with(|ctx| {
-        let ctx = ctx.borrow_mut();
-        let prev = ctx.take();
+    let _reset = Reset(set(handle.clone(), enter_context));
 
-        *ctx = Some(handle.clone());
+    f()
+}
 
-        Reset(prev)
-    });
+/// A snapshot of the runtime context active on the thread that submitted a
+/// blocking closure, taken at submission time so it can be restored on the
+/// pool thread that actually runs it.
+///
+/// The thread-local [`CONTEXT`] is per thread, so without this, the pool
+/// thread running a `spawn_blocking` closure has no context set and the
+/// closure has no way to observe [`Handle::current`] or spawn further tasks.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockingContext(Option<ThreadContext>);
 
-    f()
+/// Capture the runtime context active on the current thread, if any.
+pub(crate) fn capture_for_blocking() -> BlockingContext {
+    BlockingContext(CONTEXT.with(|ctx| ctx.borrow().clone()))
+}
+
+impl BlockingContext {
+    /// Restores the captured context on the current thread for the
+    /// lifetime of the returned guard, tagging it as entered for blocking
+    /// work rather than as a worker.
+    ///
+    /// Returns `None` if nothing was captured, i.e. the submitting thread
+    /// itself had no runtime context.
+    pub(crate) fn enter(&self) -> Option<EnterGuard> {
+        let ctx = self.0.clone()?;
+        Some(EnterGuard(set(ctx.handle, EnterContext::Blocking)))
+    }
 }
 
 #[cfg(all(feature = "io-driver", not(loom)))]
 pub(crate) fn io_handle() -> io::Handle {
     CONTEXT.with(|ctx| match *ctx.borrow() {
-        Some(ref handle) => handle.io_handle.clone(),
+        Some(ref ctx) => ctx.handle.io_handle.clone(),
         None => None,
     })
 }
@@ -45,7 +211,7 @@ pub(crate) fn io_handle() -> io::Handle {
 #[cfg(all(feature = "time", not(loom)))]
 pub(crate) fn time_handle() -> time::Handle {
     CONTEXT.with(|ctx| match *ctx.borrow() {
-        Some(ref handle) => handle.time_handle.clone(),
+        Some(ref ctx) => ctx.handle.time_handle.clone(),
         None => None,
     })
 }
@@ -53,7 +219,7 @@ pub(crate) fn time_handle() -> time::Handle {
 #[cfg(feature = "rt-core")]
 pub(crate) fn spawn_handle() -> Option<Spawner> {
     CONTEXT.with(|ctx| match *ctx.borrow() {
-        Some(ref handle) => Some(handle.spawner.clone()),
+        Some(ref ctx) => Some(ctx.handle.spawner.clone()),
         None => None,
     })
 }
@@ -61,7 +227,7 @@ pub(crate) fn spawn_handle() -> Option<Spawner> {
 #[cfg(all(feature = "test-util", feature = "time"))]
 pub(crate) fn clock() -> Option<time::Clock> {
     CONTEXT.with(|ctx| match *ctx.borrow() {
-        Some(ref handle) => Some(handle.clock.clone()),
+        Some(ref ctx) => Some(ctx.handle.clock.clone()),
         None => None,
     })
 }
@@ -86,16 +252,19 @@ where
         let mut ctx = cell.borrow_mut();
 
         let prev = ctx.take();
-        let mut next = prev.clone().unwrap_or_else(|| Handle {
-            spawner: Spawner::Shell,
-            io_handle: Default::default(),
-            time_handle: Default::default(),
-            clock: Clock::new(),
-            blocking_spawner: Default::default()
+        let mut next = prev.clone().unwrap_or_else(|| ThreadContext {
+            handle: Handle {
+                spawner: Spawner::Shell,
+                io_handle: Default::default(),
+                time_handle: Default::default(),
+                clock: Clock::new(),
+                blocking_spawner: Default::default()
+            },
+            enter: EnterContext::Worker,
         });
 
-        next.time_handle = time;
-        next.clock = clock;
+        next.handle.time_handle = time;
+        next.handle.clock = clock;
 
         *ctx = Some(next);
 